@@ -1,17 +1,128 @@
-use std::{cmp::min, fs::File, io::Write, path::PathBuf, thread::sleep, time::Duration};
+use std::{
+    cmp::min,
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    sync::OnceLock,
+    thread::sleep,
+    time::Duration,
+};
 
 use console::{Emoji, StyledObject};
+use flate2::read::GzDecoder;
 use futures_util::StreamExt;
 use indicatif::{style::TemplateError, ProgressBar, ProgressStyle};
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use sevenz_rust::Password;
+use sha2::{Digest, Sha256};
 use steamlocate::SteamDir;
 
-static STEAM_APP_ID: u32 = 289070;
-static DLC_URL: &'static str = "https://pixeldrain.com/api/file/Csbg5SqZ?download";
-static PASSWORD: Option<&'static str> = Some("cs.rin.ru");
-static ZIP_FILE: &'static str = "dlc.7z";
+/// Bundled default package manifest, used when no `packages.toml` can be read
+/// from the current working directory.
+static DEFAULT_PACKAGES_TOML: &'static str = include_str!("packages.toml");
+static PACKAGES_PATH: &'static str = "packages.toml";
+
 static DELETE_AFTER: bool = true;
+static STREAM_TO_MEMORY: bool = false;
+
+/// Whether status updates are also emitted as JSON lines on stdout, so a
+/// wrapping GUI (Tauri/egui/etc.) can drive its own progress UI. Enabled at
+/// runtime with a `--json` flag or a truthy `JSON_OUTPUT` env var, since the
+/// installer ships as a single prebuilt binary nobody wants to recompile.
+fn json_output_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::args().any(|arg| arg == "--json")
+            || std::env::var("JSON_OUTPUT")
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false)
+    })
+}
+
+/// A single machine-readable progress update, one JSON object per line.
+#[derive(Debug, Serialize)]
+struct Status {
+    phase: &'static str,
+    label: &'static str,
+    progress: Option<u64>,
+    total: Option<u64>,
+    complete: bool,
+    error: Option<String>,
+}
+
+/// Emit a [`Status`] event as a JSON line on stdout, if JSON output is enabled.
+fn emit_status(
+    phase: &'static str,
+    label: &'static str,
+    progress: Option<u64>,
+    total: Option<u64>,
+    complete: bool,
+    error: Option<String>,
+) {
+    if !json_output_enabled() {
+        return;
+    }
+    let status = Status {
+        phase,
+        label,
+        progress,
+        total,
+        complete,
+        error,
+    };
+    if let Ok(json) = serde_json::to_string(&status) {
+        println!("{json}");
+    }
+}
+
+/// A Steam title's DLC, and everything needed to fetch and install it.
+#[derive(Debug, Deserialize)]
+struct Package {
+    /// Shown to the user when picking between multiple packages.
+    name: String,
+    app_id: u32,
+    url: String,
+    /// Additional URLs to fall back to if `url` can't be reached.
+    #[serde(default)]
+    mirrors: Vec<String>,
+    password: Option<String>,
+    /// Filename the archive is stored under while installing.
+    archive: String,
+    /// Expected SHA-256 digest of the downloaded archive, if known. Left
+    /// unset, verification is skipped rather than failing every install
+    /// against a value nobody has confirmed.
+    checksum: Option<String>,
+}
+
+/// The on-disk/bundled shape of a package manifest.
+#[derive(Debug, Deserialize)]
+struct PackageManifest {
+    packages: Vec<Package>,
+}
+
+/// The archive formats an installable package can be distributed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveFormat {
+    SevenZ,
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Work out the format of an archive from its filename, falling back to
+    /// 7z since that's what the original pixeldrain mirrors use.
+    fn from_filename(name: &str) -> Self {
+        let lower = name.to_lowercase();
+        if lower.ends_with(".zip") {
+            Self::Zip
+        } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+            Self::TarGz
+        } else {
+            Self::SevenZ
+        }
+    }
+}
 
 static LOOKING_GLASS: Emoji<'_, '_> = Emoji("🔍", "");
 static TRUCK: Emoji<'_, '_> = Emoji("🚚", "");
@@ -24,10 +135,10 @@ static SPARKLE: Emoji<'_, '_> = Emoji("✨", "");
 enum Error {
     #[error("the steam install directory could not be found")]
     SteamNotFound,
-    #[error("the civ6 install directory could not be found")]
-    Civ6NotFound,
-    #[error("failed to find the parent directory of the civ6 install directory")]
-    Civ6NoParent,
+    #[error("the game's install directory could not be found")]
+    GameNotFound,
+    #[error("failed to find the parent directory of the game's install directory")]
+    GameNoParent,
 
     #[error("failed to download the dlc: {0}")]
     DownloadDlc(#[from] reqwest::Error),
@@ -37,6 +148,12 @@ enum Error {
     CreateFile(std::io::Error),
     #[error("failed to download a chunk: {0}")]
     DownloadChunk(std::io::Error),
+    #[error("failed to rename the partial download to its final name: {0}")]
+    RenamePartial(std::io::Error),
+    #[error("checksum mismatch, expected {expected} but got {got}")]
+    ChecksumMismatch { expected: String, got: String },
+    #[error("download incomplete, got {downloaded} of {total} bytes")]
+    IncompleteDownload { downloaded: u64, total: u64 },
 
     #[error("failed to get the length of the dlc zip: {0}")]
     LengthDlc(std::io::Error),
@@ -44,11 +161,24 @@ enum Error {
     Template(#[from] TemplateError),
     #[error("no parent directory found for the 7z file")]
     NoParent7z,
+    #[error("downloaded file is not a valid 7z archive: {0}")]
+    Not7zArchive(sevenz_rust::Error),
     #[error("failed to extract the dlc: {0}")]
     ExtractDlc(#[from] sevenz_rust::Error),
+    #[error("downloaded file is not a valid zip archive: {0}")]
+    NotZipArchive(zip::result::ZipError),
+    #[error("failed to extract the zip: {0}")]
+    ExtractZip(#[from] zip::result::ZipError),
 
     #[error("an io error occurred: {0}")]
     Io(#[from] std::io::Error),
+
+    #[error("failed to parse the packages manifest: {0}")]
+    ParsePackages(#[from] toml::de::Error),
+    #[error("no packages are configured")]
+    NoPackages,
+    #[error("stdin closed before a package was chosen")]
+    Stdin,
 }
 
 /// Transforms the progress to bold and dim.
@@ -64,8 +194,8 @@ fn spinner(pb: &ProgressBar, length: u64, ticks: u64) {
     }
 }
 
-/// Grab the Civ6 install directory.
-fn civ6_install(progress: &'static str) -> Result<PathBuf, Error> {
+/// Grab a Steam game's install directory from its app id.
+fn game_install(progress: &'static str, app_id: u32) -> Result<PathBuf, Error> {
     let pb = ProgressBar::new_spinner();
 
     pb.set_message(format!(
@@ -73,6 +203,7 @@ fn civ6_install(progress: &'static str) -> Result<PathBuf, Error> {
         progress_style(progress),
         LOOKING_GLASS
     ));
+    emit_status("game_install", "Finding Steam install directory...", None, None, false, None);
     let mut steam_dir = SteamDir::locate().ok_or(Error::SteamNotFound)?;
     spinner(&pb, 500, 10);
     pb.set_message(format!(
@@ -82,35 +213,107 @@ fn civ6_install(progress: &'static str) -> Result<PathBuf, Error> {
     ));
 
     pb.set_message(format!(
-        "{} {} Finding Civ6 install directory...",
+        "{} {} Finding the game's install directory...",
         progress_style(progress),
         LOOKING_GLASS
     ));
-    let civ6 = steam_dir.app(&STEAM_APP_ID).ok_or(Error::Civ6NotFound)?;
-    let path = civ6.path.parent().ok_or(Error::Civ6NoParent)?.to_path_buf();
+    emit_status("game_install", "Finding the game's install directory...", None, None, false, None);
+    let game = steam_dir.app(&app_id).ok_or(Error::GameNotFound)?;
+    let path = game.path.parent().ok_or(Error::GameNoParent)?.to_path_buf();
     spinner(&pb, 500, 10);
     pb.finish_and_clear();
     println!(
-        "{} {} Found Civ6 install directory!",
+        "{} {} Found the game's install directory!",
         progress_style(progress),
         SPARKLE
     );
+    emit_status("game_install", "Found the game's install directory!", None, None, true, None);
 
     Ok(path)
 }
 
-/// Download a file from a URL.
+/// Load the list of configured packages, preferring an overriding
+/// `packages.toml` on disk over the bundled default.
+fn load_packages() -> Result<Vec<Package>, Error> {
+    let raw = std::fs::read_to_string(PACKAGES_PATH)
+        .unwrap_or_else(|_| DEFAULT_PACKAGES_TOML.to_string());
+    let manifest: PackageManifest = toml::from_str(&raw)?;
+    Ok(manifest.packages)
+}
+
+/// Ask the user to pick a package when more than one is configured.
+fn pick_package(packages: &[Package]) -> Result<&Package, Error> {
+    match packages {
+        [] => Err(Error::NoPackages),
+        [package] => Ok(package),
+        packages => {
+            println!("Multiple packages are configured, which would you like to install?");
+            for (i, package) in packages.iter().enumerate() {
+                println!("{}. {}", i + 1, package.name);
+            }
+
+            loop {
+                let mut input = String::new();
+                // `read_line` returns `Ok(0)` rather than an `Err` on EOF
+                // (stdin closed/redirected from /dev/null), which would
+                // otherwise spin this loop forever without a terminal attached.
+                if std::io::stdin().read_line(&mut input)? == 0 {
+                    return Err(Error::Stdin);
+                }
+                if let Ok(choice) = input.trim().parse::<usize>() {
+                    if choice >= 1 && choice <= packages.len() {
+                        return Ok(&packages[choice - 1]);
+                    }
+                }
+                println!("Please enter a number between 1 and {}.", packages.len());
+            }
+        }
+    }
+}
+
+/// Download a file from a URL, resuming from a `<path>.partial` file if one is
+/// already present on disk.
 ///
 /// Stolen from: https://gist.github.com/Tapanhaz/096e299bf060607b572d700e89a62529 (with changes)
 async fn download_file(
     client: &Client,
     url: &str,
     path: &str,
+    label: &'static str,
     start: String,
     done: String,
 ) -> Result<File, Error> {
-    let res = client.get(url).send().await?;
-    let total_size = res.content_length().ok_or(Error::ContentLength)?;
+    let partial_path = format!("{path}.partial");
+    let existing = std::fs::metadata(&partial_path).map(|m| m.len()).unwrap_or(0);
+
+    // Ask for the remainder of the file if we already have some of it
+    let mut req = client.get(url);
+    if existing > 0 {
+        req = req.header(reqwest::header::RANGE, format!("bytes={existing}-"));
+    }
+    // A dead mirror answering with a 4xx/5xx error page (which may still
+    // carry a Content-Length) must not be mistaken for the archive.
+    let res = req.send().await?.error_for_status()?;
+
+    // The server may ignore the range and send the whole file back as a 200
+    let resumed = existing > 0 && res.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let remaining = res.content_length().ok_or(Error::ContentLength)?;
+
+    // Prefer the real total from Content-Range, falling back to what we can infer
+    let total_size = if resumed {
+        res.headers()
+            .get(reqwest::header::CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.rsplit('/').next())
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(existing + remaining)
+    } else {
+        remaining
+    };
+
+    // A partial file bigger than the advertised total is corrupt, start over
+    let resumed = resumed && existing <= total_size;
+    let mut downloaded = if resumed { existing } else { 0 };
 
     // Indicatif setup
     let pb = ProgressBar::new(total_size);
@@ -118,10 +321,17 @@ async fn download_file(
         .template("{msg}\n{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")?
         .progress_chars("#>-"));
     pb.set_message(start);
-
-    // Download all of the chunks
-    let mut file = File::create(path).map_err(Error::CreateFile)?;
-    let mut downloaded: u64 = 0;
+    pb.set_position(downloaded);
+    emit_status("download_dlc", label, Some(downloaded), Some(total_size), false, None);
+
+    // Download all of the chunks into the partial file, appending if resuming
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&partial_path)
+        .map_err(Error::CreateFile)?;
     let mut stream = res.bytes_stream();
 
     while let Some(item) = stream.next().await {
@@ -130,29 +340,57 @@ async fn download_file(
         let new = min(downloaded + (chunk.len() as u64), total_size);
         downloaded = new;
         pb.set_position(new);
+        emit_status("download_dlc", label, Some(downloaded), Some(total_size), false, None);
+    }
+
+    // The stream can end early (server hangup, truncated response, ...)
+    // without a hard error, so don't report success unless we actually got
+    // everything that was promised.
+    if downloaded != total_size {
+        let error = Error::IncompleteDownload {
+            downloaded,
+            total: total_size,
+        };
+        pb.finish_and_clear();
+        emit_status(
+            "download_dlc",
+            label,
+            Some(downloaded),
+            Some(total_size),
+            true,
+            Some(error.to_string()),
+        );
+        return Err(error);
     }
+    std::fs::rename(&partial_path, path).map_err(Error::RenamePartial)?;
 
     // Done
     pb.finish_and_clear();
     println!("{done}");
-    return Ok(file);
+    emit_status("download_dlc", label, Some(downloaded), Some(total_size), true, None);
+    return Ok(File::open(path)?);
 }
 
 /// Extract a 7z file with a progress bar.
-fn extract_7z(
-    file: File,
+///
+/// Accepts anything readable and seekable, so the archive can come from an
+/// on-disk file or straight from an in-memory buffer.
+fn extract_7z<R: Read + Seek>(
+    mut file: R,
     password: Option<String>,
     dest: PathBuf,
+    label: &'static str,
     start: String,
     done: String,
 ) -> Result<(), Error> {
     // Initialise the 7z reader
-    let len = file.metadata().map(|m| m.len()).map_err(Error::LengthDlc)?;
+    let len = file.seek(SeekFrom::End(0)).map_err(Error::LengthDlc)?;
+    file.seek(SeekFrom::Start(0)).map_err(Error::LengthDlc)?;
     let password = match password {
         Some(x) => Password::from(x.as_str()),
         None => Password::empty(),
     };
-    let mut sz = sevenz_rust::SevenZReader::new(file, len, password)?;
+    let mut sz = sevenz_rust::SevenZReader::new(file, len, password).map_err(Error::Not7zArchive)?;
 
     // Get the total size of the archive
     let archive_size: u64 = sz
@@ -169,6 +407,7 @@ fn extract_7z(
         .template("{msg}\n{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")?
         .progress_chars("#>-"));
     pb.set_message(start);
+    emit_status("extract_dlc", label, Some(0), Some(archive_size), false, None);
 
     // Read each entry and extract it
     let mut uncompressed_size = 0;
@@ -206,67 +445,463 @@ fn extract_7z(
             file.write_all(&buf[..read_size])?;
             uncompressed_size += read_size;
             pb.set_position(uncompressed_size as u64);
+            emit_status("extract_dlc", label, Some(uncompressed_size as u64), Some(archive_size), false, None);
         }
     })?;
 
-    // Clean up and finish
-    if DELETE_AFTER {
-        std::fs::remove_file(ZIP_FILE)?;
+    pb.finish_and_clear();
+    println!("{done}");
+    emit_status("extract_dlc", label, Some(archive_size), Some(archive_size), true, None);
+    Ok(())
+}
+
+/// Extract a zip file with a progress bar, mirroring [`extract_7z`]'s
+/// "skip existing files except `.dll`" overwrite policy.
+fn extract_zip<R: Read + Seek>(
+    file: R,
+    dest: PathBuf,
+    label: &'static str,
+    start: String,
+    done: String,
+) -> Result<(), Error> {
+    let mut archive = zip::ZipArchive::new(file).map_err(Error::NotZipArchive)?;
+
+    // Get the total size of the archive
+    let archive_size: u64 = (0..archive.len())
+        .map(|i| archive.by_index(i).map(|e| e.size()).unwrap_or(0))
+        .sum();
+
+    // Indicatif setup
+    let pb = ProgressBar::new(archive_size);
+    pb.set_style(ProgressStyle::default_bar()
+        .template("{msg}\n{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")?
+        .progress_chars("#>-"));
+    pb.set_message(start);
+    emit_status("extract_dlc", label, Some(0), Some(archive_size), false, None);
+
+    // Read each entry and extract it
+    let mut uncompressed_size = 0u64;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let path = match entry.enclosed_name() {
+            Some(name) => dest.join(name),
+            None => continue,
+        };
+
+        // Check if directory
+        if entry.is_dir() {
+            std::fs::create_dir_all(&path)?;
+            continue;
+        }
+
+        // Ignore if the file exists, ignore for dlls
+        if path.exists() && path.extension().map(|x| x != "dll").unwrap_or(true) {
+            continue;
+        }
+
+        // Create the parent directory(s)
+        std::fs::create_dir_all(path.parent().ok_or(Error::NoParent7z)?)?;
+
+        // Write the entry to the file
+        let mut out = File::create(&path)?;
+        let mut buf = [0u8; 1024];
+        loop {
+            let read_size = entry.read(&mut buf)?;
+            if read_size == 0 {
+                break;
+            }
+            out.write_all(&buf[..read_size])?;
+            uncompressed_size += read_size as u64;
+            pb.set_position(uncompressed_size);
+            emit_status("extract_dlc", label, Some(uncompressed_size), Some(archive_size), false, None);
+        }
     }
+
     pb.finish_and_clear();
     println!("{done}");
+    emit_status("extract_dlc", label, Some(archive_size), Some(archive_size), true, None);
     Ok(())
 }
 
-/// Download the DLC zip.
-async fn download_dlc(progress: &'static str) -> Result<File, Error> {
-    // Check if the file is already present
-    if PathBuf::from(ZIP_FILE).exists() {
-        println!(
-            "{} {} The DLC zip is already downloaded!",
-            progress_style(progress),
-            SPARKLE
-        );
-        return Ok(File::open(ZIP_FILE)?);
+/// Resolve an archive entry's path against `dest`, rejecting entries that
+/// would escape it (absolute paths, `..`, Windows path prefixes). Mirrors
+/// what `enclosed_name()` already does for the zip backend; the tar crate
+/// applies no such sanitization of its own.
+fn enclosed_path(dest: &std::path::Path, entry_path: &std::path::Path) -> Option<PathBuf> {
+    use std::path::Component;
+
+    let mut out = dest.to_path_buf();
+    for component in entry_path.components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
     }
+    Some(out)
+}
 
-    download_file(
-        &reqwest::Client::new(),
-        DLC_URL,
-        &ZIP_FILE,
-        format!(
-            "{} {} Downloading the DLC zip...",
-            progress_style(progress),
-            TRUCK
-        ),
-        format!(
-            "{} {} Downloaded the DLC zip!",
-            progress_style(progress),
-            SPARKLE
-        ),
-    )
-    .await
+/// Extract a gzip-compressed tarball with a spinner, mirroring [`extract_7z`]'s
+/// "skip existing files except `.dll`" overwrite policy.
+///
+/// The total uncompressed size isn't known up-front for a streamed tarball,
+/// so progress is reported by entry count rather than bytes.
+fn extract_tar_gz<R: Read>(
+    file: R,
+    dest: PathBuf,
+    label: &'static str,
+    start: String,
+    done: String,
+) -> Result<(), Error> {
+    let pb = ProgressBar::new_spinner();
+    pb.set_message(start);
+    emit_status("extract_dlc", label, None, None, false, None);
+
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut extracted: u64 = 0;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.into_owned();
+        let path = match enclosed_path(&dest, &entry_path) {
+            Some(path) => path,
+            None => continue,
+        };
+
+        // Check if directory
+        if entry.header().entry_type().is_dir() {
+            std::fs::create_dir_all(&path)?;
+            continue;
+        }
+
+        // Ignore if the file exists, ignore for dlls
+        if path.exists() && path.extension().map(|x| x != "dll").unwrap_or(true) {
+            continue;
+        }
+
+        // Create the parent directory(s)
+        std::fs::create_dir_all(path.parent().ok_or(Error::NoParent7z)?)?;
+
+        // Write the entry to the file
+        let mut out = File::create(&path)?;
+        std::io::copy(&mut entry, &mut out)?;
+
+        extracted += 1;
+        pb.tick();
+        emit_status("extract_dlc", label, Some(extracted), None, false, None);
+    }
+
+    pb.finish_and_clear();
+    println!("{done}");
+    emit_status("extract_dlc", label, Some(extracted), Some(extracted), true, None);
+    Ok(())
+}
+
+/// Hash a readable/seekable source and compare it against an expected SHA-256
+/// digest, rewinding it afterwards so it can still be consumed by the caller.
+fn verify_checksum<R: Read + Seek>(
+    progress: &'static str,
+    mut file: R,
+    expected: &str,
+) -> Result<R, Error> {
+    let len = file.seek(SeekFrom::End(0)).map_err(Error::LengthDlc)?;
+    file.seek(SeekFrom::Start(0)).map_err(Error::LengthDlc)?;
+
+    // Indicatif setup
+    let pb = ProgressBar::new(len);
+    pb.set_style(ProgressStyle::default_bar()
+        .template("{msg}\n{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")?
+        .progress_chars("#>-"));
+    pb.set_message(format!(
+        "{} {} Verifying the DLC zip...",
+        progress_style(progress),
+        LOOKING_GLASS
+    ));
+    emit_status("download_dlc", "Verifying the DLC zip...", Some(0), Some(len), false, None);
+
+    // Hash the file in chunks
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 8192];
+    let mut hashed: u64 = 0;
+    loop {
+        let read_size = file.read(&mut buf)?;
+        if read_size == 0 {
+            break;
+        }
+        hasher.update(&buf[..read_size]);
+        hashed += read_size as u64;
+        pb.set_position(hashed);
+        emit_status("download_dlc", "Verifying the DLC zip...", Some(hashed), Some(len), false, None);
+    }
+    file.seek(SeekFrom::Start(0))?;
+    pb.finish_and_clear();
+
+    let got = format!("{:x}", hasher.finalize());
+    if got != expected {
+        let error = Error::ChecksumMismatch {
+            expected: expected.to_string(),
+            got,
+        };
+        emit_status("download_dlc", "Verifying the DLC zip...", None, None, true, Some(error.to_string()));
+        return Err(error);
+    }
+
+    println!(
+        "{} {} DLC zip checksum verified!",
+        progress_style(progress),
+        SPARKLE
+    );
+    emit_status("download_dlc", "DLC zip checksum verified!", Some(len), Some(len), true, None);
+    Ok(file)
 }
 
-/// Extract the DLC zip.
-fn extract_dlc(progress: &'static str, file: File, dest: PathBuf) -> Result<(), Error> {
-    extract_7z(
-        file,
-        PASSWORD.map(|x| x.to_string()),
-        dest,
-        format!(
-            "{} {} Extracting the DLC zip...",
-            progress_style(progress),
-            CLIP
-        ),
-        format!(
-            "{} {} Extracted the DLC zip!",
-            progress_style(progress),
-            SPARKLE
-        ),
+/// A package's download URL followed by its mirrors, in the order they
+/// should be tried.
+fn package_urls(package: &Package) -> Vec<&str> {
+    std::iter::once(package.url.as_str())
+        .chain(package.mirrors.iter().map(String::as_str))
+        .collect()
+}
+
+/// True if a download failure is worth retrying against the next mirror,
+/// rather than giving up immediately.
+fn is_mirror_failure(err: &Error) -> bool {
+    matches!(
+        err,
+        Error::DownloadDlc(_)
+            | Error::ContentLength
+            | Error::ChecksumMismatch { .. }
+            | Error::IncompleteDownload { .. }
     )
 }
 
+/// Download a package's DLC zip.
+async fn download_dlc(progress: &'static str, package: &Package) -> Result<File, Error> {
+    // Check if the file is already present, re-downloading it if it fails verification
+    if PathBuf::from(&package.archive).exists() {
+        match &package.checksum {
+            Some(checksum) => {
+                println!(
+                    "{} {} The DLC zip is already downloaded, verifying it...",
+                    progress_style(progress),
+                    SPARKLE
+                );
+                match verify_checksum(progress, File::open(&package.archive)?, checksum) {
+                    Ok(file) => return Ok(file),
+                    Err(Error::ChecksumMismatch { .. }) => {
+                        println!(
+                            "{} {} The existing DLC zip is corrupt, re-downloading it...",
+                            progress_style(progress),
+                            LOOKING_GLASS
+                        );
+                        std::fs::remove_file(&package.archive)?;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            None => {
+                println!(
+                    "{} {} The DLC zip is already downloaded (no checksum configured, skipping verification)",
+                    progress_style(progress),
+                    SPARKLE
+                );
+                return Ok(File::open(&package.archive)?);
+            }
+        }
+    }
+
+    let client = reqwest::Client::new();
+    let urls = package_urls(package);
+    let partial_path = format!("{}.partial", package.archive);
+    let mut last_err = None;
+    let mut file = None;
+
+    for (i, url) in urls.iter().enumerate() {
+        let attempt: Result<File, Error> = async {
+            let f = download_file(
+                &client,
+                url,
+                &package.archive,
+                "Downloading the DLC zip...",
+                format!(
+                    "{} {} Downloading the DLC zip...",
+                    progress_style(progress),
+                    TRUCK
+                ),
+                format!(
+                    "{} {} Downloaded the DLC zip!",
+                    progress_style(progress),
+                    SPARKLE
+                ),
+            )
+            .await?;
+            match &package.checksum {
+                Some(checksum) => verify_checksum(progress, f, checksum),
+                None => Ok(f),
+            }
+        }
+        .await;
+
+        match attempt {
+            Ok(f) => {
+                if i > 0 {
+                    println!(
+                        "{} {} Downloaded via mirror #{}: {}",
+                        progress_style(progress),
+                        SPARKLE,
+                        i,
+                        url
+                    );
+                }
+                file = Some(f);
+                break;
+            }
+            Err(e) if is_mirror_failure(&e) => {
+                // Don't let the next mirror resume from (or get judged
+                // against the checksum of) bytes written by this one.
+                let _ = std::fs::remove_file(&package.archive);
+                let _ = std::fs::remove_file(&partial_path);
+                last_err = Some(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    file.ok_or_else(|| last_err.unwrap_or(Error::ContentLength))
+}
+
+/// Download a package's DLC zip straight into memory, without ever writing it to disk.
+async fn download_dlc_to_memory(
+    progress: &'static str,
+    package: &Package,
+) -> Result<std::io::Cursor<Vec<u8>>, Error> {
+    let client = reqwest::Client::new();
+    let urls = package_urls(package);
+
+    let mut last_err = None;
+    let mut buf = None;
+
+    // The whole body-streaming + verification step lives inside the per-URL
+    // attempt, same shape as `download_dlc`, so a connection drop mid-stream
+    // is retried against the next mirror instead of failing the install.
+    for (i, url) in urls.iter().enumerate() {
+        let attempt: Result<Vec<u8>, Error> = async {
+            // Same reasoning as `download_file`: reject error responses before
+            // trusting their Content-Length.
+            let res = client.get(*url).send().await?.error_for_status()?;
+            let total_size = res.content_length().ok_or(Error::ContentLength)?;
+
+            // Indicatif setup
+            let pb = ProgressBar::new(total_size);
+            pb.set_style(ProgressStyle::default_bar()
+                .template("{msg}\n{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")?
+                .progress_chars("#>-"));
+            pb.set_message(format!(
+                "{} {} Downloading the DLC zip...",
+                progress_style(progress),
+                TRUCK
+            ));
+            emit_status("download_dlc", "Downloading the DLC zip...", Some(0), Some(total_size), false, None);
+
+            // Collect all of the chunks straight into a buffer
+            let mut chunk_buf: Vec<u8> = Vec::with_capacity(total_size as usize);
+            let mut downloaded: u64 = 0;
+            let mut stream = res.bytes_stream();
+
+            while let Some(item) = stream.next().await {
+                let chunk = item?;
+                chunk_buf.extend_from_slice(&chunk);
+                let new = min(downloaded + (chunk.len() as u64), total_size);
+                downloaded = new;
+                pb.set_position(new);
+                emit_status("download_dlc", "Downloading the DLC zip...", Some(downloaded), Some(total_size), false, None);
+            }
+            pb.finish_and_clear();
+
+            if downloaded != total_size {
+                let error = Error::IncompleteDownload {
+                    downloaded,
+                    total: total_size,
+                };
+                emit_status("download_dlc", "Downloading the DLC zip...", Some(downloaded), Some(total_size), true, Some(error.to_string()));
+                return Err(error);
+            }
+
+            println!(
+                "{} {} Downloaded the DLC zip!",
+                progress_style(progress),
+                SPARKLE
+            );
+            emit_status("download_dlc", "Downloaded the DLC zip!", Some(total_size), Some(total_size), true, None);
+
+            match &package.checksum {
+                Some(checksum) => {
+                    Ok(verify_checksum(progress, std::io::Cursor::new(chunk_buf), checksum)?.into_inner())
+                }
+                None => {
+                    println!(
+                        "{} {} No checksum configured, skipping verification",
+                        progress_style(progress),
+                        LOOKING_GLASS
+                    );
+                    Ok(chunk_buf)
+                }
+            }
+        }
+        .await;
+
+        match attempt {
+            Ok(b) => {
+                if i > 0 {
+                    println!(
+                        "{} {} Downloaded via mirror #{}: {}",
+                        progress_style(progress),
+                        SPARKLE,
+                        i,
+                        url
+                    );
+                }
+                buf = Some(b);
+                break;
+            }
+            Err(e) if is_mirror_failure(&e) => last_err = Some(e),
+            Err(e) => return Err(e),
+        }
+    }
+
+    let buf = buf.ok_or_else(|| last_err.unwrap_or(Error::ContentLength))?;
+    Ok(std::io::Cursor::new(buf))
+}
+
+/// Extract a package's DLC archive, picking the backend from its filename.
+///
+/// Accepts anything readable and seekable, so it can extract directly from an
+/// in-memory download as well as from the file-backed path.
+fn extract_dlc<R: Read + Seek>(
+    progress: &'static str,
+    file: R,
+    dest: PathBuf,
+    password: Option<String>,
+    archive: &str,
+) -> Result<(), Error> {
+    let label = "Extracting the DLC zip...";
+    let start = format!(
+        "{} {} Extracting the DLC zip...",
+        progress_style(progress),
+        CLIP
+    );
+    let done = format!(
+        "{} {} Extracted the DLC zip!",
+        progress_style(progress),
+        SPARKLE
+    );
+
+    match ArchiveFormat::from_filename(archive) {
+        ArchiveFormat::SevenZ => extract_7z(file, password, dest, label, start, done),
+        ArchiveFormat::Zip => extract_zip(file, dest, label, start, done),
+        ArchiveFormat::TarGz => extract_tar_gz(file, dest, label, start, done),
+    }
+}
+
 /// Pause, by waiting for input.
 fn pause() {
     let mut input = String::new();
@@ -278,7 +913,7 @@ async fn main_inner() -> Result<(), Error> {
     std::env::set_var("WT_SESSION", "1");
 
     println!(
-"Welcome to the Civ6 DLC downloader!
+"Welcome to the Steam DLC downloader!
 Created by: Stefanuk12
 
 NOTE: You need the following:
@@ -291,13 +926,37 @@ Press enter to continue..."
 
     pause();
 
-    let civ6 = civ6_install("[1/4]")?;
-    let dlc_zip = download_dlc("[2/4]").await?;
-    extract_dlc("[3/4]", dlc_zip, civ6)?;
+    let packages = load_packages()?;
+    let package = pick_package(&packages)?;
+
+    let install_dir = game_install("[1/4]", package.app_id)?;
+    if STREAM_TO_MEMORY {
+        let dlc_zip = download_dlc_to_memory("[2/4]", package).await?;
+        extract_dlc(
+            "[3/4]",
+            dlc_zip,
+            install_dir,
+            package.password.clone(),
+            &package.archive,
+        )?;
+    } else {
+        let dlc_zip = download_dlc("[2/4]", package).await?;
+        extract_dlc(
+            "[3/4]",
+            dlc_zip,
+            install_dir,
+            package.password.clone(),
+            &package.archive,
+        )?;
+        if DELETE_AFTER {
+            std::fs::remove_file(&package.archive)?;
+        }
+    }
     println!(
-        "{} {} Done, you can now run Civ6 with all of the DLC!",
+        "{} {} Done, you can now run {} with all of the DLC!",
         progress_style("[4/4]"),
-        PAPER
+        PAPER,
+        package.name
     );
 
     pause();
@@ -311,6 +970,7 @@ async fn main() {
         Ok(_) => (),
         Err(e) => {
             eprintln!("Error: {}", e.to_string());
+            emit_status("main", "Error", None, None, true, Some(e.to_string()));
             pause();
         }
     }